@@ -0,0 +1,387 @@
+use crate::clean_word;
+use crate::output::{OutputFormat, SortOrder};
+use std::collections::HashSet;
+
+//Parsed command-line configuration. Fields default to the original
+//fixed behavior (lowercase everything, no stop words, no limits) so
+//that omitting every flag reproduces the old output exactly.
+pub struct Config {
+    pub ignore: HashSet<String>,
+    pub top: Option<usize>,
+    pub case_sensitive: bool,
+    pub min_count: usize,
+    pub ngram: usize,
+    pub segment: bool,
+    pub corpus: Option<String>,
+    pub max_word_len: usize,
+    pub threads: Option<usize>,
+    pub output: OutputFormat,
+    pub sort: SortOrder,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            ignore: HashSet::new(),
+            top: None,
+            case_sensitive: false,
+            min_count: 0,
+            ngram: 1,
+            segment: false,
+            corpus: None,
+            max_word_len: 10,
+            threads: None,
+            output: OutputFormat::Plain,
+            sort: SortOrder::CountDesc,
+        }
+    }
+}
+
+//Either a ready-to-run Config, or a request to print --help and exit
+//without reading any input.
+pub enum CliAction {
+    Run(Config),
+    Help,
+}
+
+pub fn usage() -> &'static str {
+    "freq-rust - count word frequencies from standard input\n\
+     \n\
+     USAGE:\n\
+     \x20   freq-rust [FLAGS]\n\
+     \n\
+     FLAGS:\n\
+     \x20   --ignore WORD...     Skip these words (matched after cleaning and,\n\
+     \x20                        unless --case-sensitive, lowercasing)\n\
+     \x20   --top N              Only print the N highest-frequency entries\n\
+     \x20   --case-sensitive     Do not lowercase words before counting them\n\
+     \x20   --min-count K        Drop words that occur fewer than K times\n\
+     \x20   --ngram N            Count contiguous N-word windows instead of\n\
+     \x20                        single words (default 1)\n\
+     \x20   --segment            Split space-free input into words using\n\
+     \x20                        unigram/bigram statistics learned from --corpus\n\
+     \x20                        (for scripts like Chinese that don't use spaces)\n\
+     \x20   --corpus FILE        Text file to learn word statistics from, required\n\
+     \x20                        by --segment\n\
+     \x20   --max-word-len L     Longest candidate word --segment will consider\n\
+     \x20                        (default 10)\n\
+     \x20   --threads N          Count in parallel across N worker threads\n\
+     \x20                        (default unigram mode only)\n\
+     \x20   --output FORMAT      plain, csv, tsv, or json (default plain)\n\
+     \x20   --sort ORDER         count-desc, count-asc, alpha, or length\n\
+     \x20                        (default count-desc; ties break alphabetically)\n\
+     \x20   --help               Print this message and exit\n"
+}
+
+//Parses an argument list (conventionally std::env::args() with the
+//binary name already skipped). --ignore consumes every following
+//argument up to the next flag, since it takes a variable-length list
+//of stop words.
+pub fn parse_args<I: Iterator<Item = String>>(args: I) -> Result<CliAction, String> {
+    let mut config = Config::default();
+    let mut args = args.peekable();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--help" => return Ok(CliAction::Help),
+            "--ignore" => {
+                while let Some(next) = args.peek() {
+                    if next.starts_with("--") {
+                        break;
+                    }
+                    config.ignore.insert(args.next().unwrap());
+                }
+            }
+            "--top" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| "--top requires a number".to_owned())?;
+                config.top = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("--top expects a number, got '{}'", value))?,
+                );
+            }
+            "--case-sensitive" => {
+                config.case_sensitive = true;
+            }
+            "--min-count" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| "--min-count requires a number".to_owned())?;
+                config.min_count = value
+                    .parse()
+                    .map_err(|_| format!("--min-count expects a number, got '{}'", value))?;
+            }
+            "--ngram" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| "--ngram requires a number".to_owned())?;
+                config.ngram = value
+                    .parse()
+                    .map_err(|_| format!("--ngram expects a number, got '{}'", value))?;
+                if config.ngram == 0 {
+                    return Err("--ngram must be at least 1".to_owned());
+                }
+            }
+            "--segment" => {
+                config.segment = true;
+            }
+            "--corpus" => {
+                config.corpus = Some(
+                    args.next()
+                        .ok_or_else(|| "--corpus requires a file path".to_owned())?,
+                );
+            }
+            "--max-word-len" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| "--max-word-len requires a number".to_owned())?;
+                config.max_word_len = value
+                    .parse()
+                    .map_err(|_| format!("--max-word-len expects a number, got '{}'", value))?;
+                if config.max_word_len == 0 {
+                    return Err("--max-word-len must be at least 1".to_owned());
+                }
+            }
+            "--threads" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| "--threads requires a number".to_owned())?;
+                let threads: usize = value
+                    .parse()
+                    .map_err(|_| format!("--threads expects a number, got '{}'", value))?;
+                if threads == 0 {
+                    return Err("--threads must be at least 1".to_owned());
+                }
+                config.threads = Some(threads);
+            }
+            "--output" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| "--output requires a format".to_owned())?;
+                config.output = match value.as_str() {
+                    "plain" => OutputFormat::Plain,
+                    "csv" => OutputFormat::Csv,
+                    "tsv" => OutputFormat::Tsv,
+                    "json" => OutputFormat::Json,
+                    other => {
+                        return Err(format!(
+                            "--output expects plain, csv, tsv, or json, got '{}'",
+                            other
+                        ))
+                    }
+                };
+            }
+            "--sort" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| "--sort requires an order".to_owned())?;
+                config.sort = match value.as_str() {
+                    "count-desc" => SortOrder::CountDesc,
+                    "count-asc" => SortOrder::CountAsc,
+                    "alpha" => SortOrder::Alpha,
+                    "length" => SortOrder::Length,
+                    other => {
+                        return Err(format!(
+                            "--sort expects count-desc, count-asc, alpha, or length, got '{}'",
+                            other
+                        ))
+                    }
+                };
+            }
+            other => {
+                return Err(format!("unrecognized flag '{}'", other));
+            }
+        }
+    }
+
+    //--ignore entries are matched against already-cleaned words, so run
+    //them through the same clean_word step (apostrophe-stripping, etc.)
+    //before lowercasing (unless --case-sensitive), regardless of which
+    //order the two flags appeared in.
+    config.ignore = config
+        .ignore
+        .iter()
+        .filter_map(|w| clean_word(w))
+        .map(|w| {
+            if config.case_sensitive {
+                w.to_owned()
+            } else {
+                w.to_lowercase()
+            }
+        })
+        .collect();
+
+    if config.segment && config.corpus.is_none() {
+        return Err("--segment requires --corpus FILE".to_owned());
+    }
+
+    Ok(CliAction::Run(config))
+}
+
+#[cfg(test)]
+mod parse_args_tests {
+    use super::{parse_args, CliAction};
+    use crate::output::{OutputFormat, SortOrder};
+
+    fn args(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn defaults_with_no_flags() {
+        match parse_args(args(&[]).into_iter()).unwrap() {
+            CliAction::Run(config) => {
+                assert!(config.ignore.is_empty());
+                assert_eq!(None, config.top);
+                assert!(!config.case_sensitive);
+                assert_eq!(0, config.min_count);
+                assert_eq!(1, config.ngram);
+                assert!(!config.segment);
+                assert_eq!(None, config.corpus);
+                assert_eq!(10, config.max_word_len);
+                assert_eq!(None, config.threads);
+                assert_eq!(OutputFormat::Plain, config.output);
+                assert_eq!(SortOrder::CountDesc, config.sort);
+            }
+            CliAction::Help => panic!("expected Run, got Help"),
+        }
+    }
+
+    #[test]
+    fn output_flag() {
+        match parse_args(args(&["--output", "json"]).into_iter()).unwrap() {
+            CliAction::Run(config) => assert_eq!(OutputFormat::Json, config.output),
+            CliAction::Help => panic!("expected Run, got Help"),
+        }
+    }
+
+    #[test]
+    fn output_rejects_unknown_format() {
+        let result = parse_args(args(&["--output", "xml"]).into_iter());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn sort_flag() {
+        match parse_args(args(&["--sort", "alpha"]).into_iter()).unwrap() {
+            CliAction::Run(config) => assert_eq!(SortOrder::Alpha, config.sort),
+            CliAction::Help => panic!("expected Run, got Help"),
+        }
+    }
+
+    #[test]
+    fn sort_rejects_unknown_order() {
+        let result = parse_args(args(&["--sort", "random"]).into_iter());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn threads_flag() {
+        match parse_args(args(&["--threads", "4"]).into_iter()).unwrap() {
+            CliAction::Run(config) => assert_eq!(Some(4), config.threads),
+            CliAction::Help => panic!("expected Run, got Help"),
+        }
+    }
+
+    #[test]
+    fn threads_rejects_zero() {
+        let result = parse_args(args(&["--threads", "0"]).into_iter());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn segment_requires_corpus() {
+        let result = parse_args(args(&["--segment"]).into_iter());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn segment_with_corpus() {
+        match parse_args(args(&["--segment", "--corpus", "corpus.txt"]).into_iter()).unwrap() {
+            CliAction::Run(config) => {
+                assert!(config.segment);
+                assert_eq!(Some("corpus.txt".to_owned()), config.corpus);
+            }
+            CliAction::Help => panic!("expected Run, got Help"),
+        }
+    }
+
+    #[test]
+    fn max_word_len_rejects_zero() {
+        let result = parse_args(args(&["--max-word-len", "0"]).into_iter());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn ngram_flag() {
+        match parse_args(args(&["--ngram", "2"]).into_iter()).unwrap() {
+            CliAction::Run(config) => assert_eq!(2, config.ngram),
+            CliAction::Help => panic!("expected Run, got Help"),
+        }
+    }
+
+    #[test]
+    fn ngram_rejects_zero() {
+        let result = parse_args(args(&["--ngram", "0"]).into_iter());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn help_short_circuits() {
+        match parse_args(args(&["--top", "5", "--help"]).into_iter()).unwrap() {
+            CliAction::Help => {}
+            CliAction::Run(_) => panic!("expected Help, got Run"),
+        }
+    }
+
+    #[test]
+    fn ignore_collects_until_next_flag() {
+        match parse_args(args(&["--ignore", "the", "a", "of", "--top", "10"]).into_iter()).unwrap()
+        {
+            CliAction::Run(config) => {
+                assert!(config.ignore.contains("the"));
+                assert!(config.ignore.contains("a"));
+                assert!(config.ignore.contains("of"));
+                assert_eq!(Some(10), config.top);
+            }
+            CliAction::Help => panic!("expected Run, got Help"),
+        }
+    }
+
+    #[test]
+    fn ignore_entries_are_cleaned_like_counted_words() {
+        match parse_args(args(&["--ignore", "'tis", "e"]).into_iter()).unwrap() {
+            CliAction::Run(config) => {
+                //'tis loses its leading apostrophe, same as a counted
+                //token would; "e" isn't 'a' or 'i' so it's dropped
+                //entirely rather than kept uncleaned.
+                assert!(config.ignore.contains("tis"));
+                assert!(!config.ignore.contains("e"));
+                assert_eq!(1, config.ignore.len());
+            }
+            CliAction::Help => panic!("expected Run, got Help"),
+        }
+    }
+
+    #[test]
+    fn case_sensitive_flag() {
+        match parse_args(args(&["--case-sensitive"]).into_iter()).unwrap() {
+            CliAction::Run(config) => assert!(config.case_sensitive),
+            CliAction::Help => panic!("expected Run, got Help"),
+        }
+    }
+
+    #[test]
+    fn min_count_rejects_non_numbers() {
+        let result = parse_args(args(&["--min-count", "nope"]).into_iter());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn unrecognized_flag_is_an_error() {
+        let result = parse_args(args(&["--bogus"]).into_iter());
+        assert!(result.is_err());
+    }
+}
@@ -34,49 +34,159 @@ Assumptions:
  around this assumption is if there are apostrophes around the one 
  non-'a' or non-'i' letter (so, 'w will be mapped to w, for instance).
 "]
+mod cli;
+mod ngram;
+mod output;
+mod parallel;
+mod segment;
+
+use cli::{CliAction, Config};
+use output::OutputFormat;
 use std::io::{BufRead, BufReader, Read, stdin};
 
 fn main() {
-    let mut map = CountTable::new();
-    read_words(stdin(), &mut map);
+    let args = std::env::args().skip(1);
 
-    let mut sorted_vec: Vec<_> = map.iter().collect();
-    sorted_vec.sort_by(|a, b| b.1.cmp(a.1));
-
-    for word in sorted_vec.iter() {
-        println!("{}: {}", word.0, word.1);
+    let config = match cli::parse_args(args) {
+        Ok(CliAction::Help) => {
+            print!("{}", cli::usage());
+            return;
+        }
+        Ok(CliAction::Run(config)) => config,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            eprint!("{}", cli::usage());
+            std::process::exit(1);
+        }
+    };
+
+    let word_label = |word: &String| word.clone();
+    let ngram_label = |words: &Vec<String>| words.join(" ");
+
+    if config.segment {
+        //--corpus is required whenever --segment is set, enforced in
+        //cli::parse_args.
+        let corpus_path = config.corpus.clone().unwrap();
+        let corpus_text = std::fs::read_to_string(&corpus_path).unwrap_or_else(|error| {
+            eprintln!(
+                "error: could not read corpus file '{}': {}",
+                corpus_path, error
+            );
+            std::process::exit(1);
+        });
+
+        let mut unigrams = CountTable::new();
+        read_words(
+            std::io::Cursor::new(corpus_text.clone()),
+            &mut unigrams,
+            &config,
+        );
+
+        let mut bigrams = ngram::NgramTable::new();
+        ngram::read_ngrams(std::io::Cursor::new(corpus_text), &mut bigrams, 2, &config);
+
+        let mut map = CountTable::new();
+        segment::read_segmented(
+            stdin(),
+            &mut map,
+            &unigrams,
+            &bigrams,
+            config.max_word_len,
+            &config,
+        );
+
+        print_table(finalize(map.iter().collect(), &config, &word_label), &config, &word_label);
+    } else if config.ngram > 1 {
+        let mut table = ngram::NgramTable::new();
+        ngram::read_ngrams(stdin(), &mut table, config.ngram, &config);
+
+        print_table(finalize(table.iter().collect(), &config, &ngram_label), &config, &ngram_label);
+    } else if let Some(threads) = config.threads {
+        let map = parallel::read_words_parallel(stdin(), &config, threads);
+
+        print_table(finalize(map.iter().collect(), &config, &word_label), &config, &word_label);
+    } else {
+        let mut map = CountTable::new();
+        read_words(stdin(), &mut map, &config);
+
+        print_table(finalize(map.iter().collect(), &config, &word_label), &config, &word_label);
     }
 }
 
 //Used a BTreeMap instead of HashMap so that words would be sorted
 //alphabetically at each frequency.
-type CountTable = std::collections::BTreeMap<String, usize>;
+pub(crate) type CountTable = std::collections::BTreeMap<String, usize>;
+
+//Shared by every output path: drop anything below --min-count, sort
+//per --sort, and keep only the top N if --top was given.
+fn finalize<'a, K>(
+    mut entries: Vec<(&'a K, &'a usize)>,
+    config: &Config,
+    label: &impl Fn(&K) -> String,
+) -> Vec<(&'a K, &'a usize)> {
+    entries.retain(|(_, &count)| count >= config.min_count);
+    entries = output::sort_entries(entries, &config.sort, label);
+
+    if let Some(top) = config.top {
+        entries.truncate(top);
+    }
+
+    entries
+}
+
+//Renders per --output. A plain-format table with nothing to show
+//prints nothing, matching the original behavior; the structured
+//formats always print their (possibly empty) envelope.
+fn print_table<K>(entries: Vec<(&K, &usize)>, config: &Config, label: &impl Fn(&K) -> String) {
+    let rendered = output::render(&entries, &config.output, label);
+
+    if !entries.is_empty() || !matches!(config.output, OutputFormat::Plain) {
+        println!("{}", rendered);
+    }
+}
+
+//Separates a line into string slices by non-alphabetic characters
+//(digits included) that AREN'T apostrophes. Shared by the unigram and n-gram readers so
+//both tokenize identically.
+pub(crate) fn split_line(line: &str) -> Vec<&str> {
+    line.splitn(line.len() + 1, |c: char| !(c.is_alphabetic()) && c != '\'')
+        .collect()
+}
+
+//Cleans a raw token and, if it survives, normalizes its case and
+//checks it against --ignore. Returns None for anything that shouldn't
+//be counted at all.
+pub(crate) fn clean_and_normalize(word: &str, config: &Config) -> Option<String> {
+    let cleaned_word = clean_word(word)?;
+
+    let normalized = if config.case_sensitive {
+        String::from(cleaned_word)
+    } else {
+        String::from(cleaned_word).to_lowercase()
+    };
+
+    if config.ignore.contains(&normalized) {
+        None
+    } else {
+        Some(normalized)
+    }
+}
 
-fn read_words<R: Read>(reader: R, mut map: &mut CountTable) {
+pub(crate) fn read_words<R: Read>(reader: R, mut map: &mut CountTable, config: &Config) {
     let mut lines = BufReader::new(reader).lines();
 
     while let Some(Ok(line)) = lines.next() {
         if let Ok(unclean_line) = line.parse::<String>() {
-            //Initial "Filter": Separate the line into string slices by
-            //non-alphanumeric characters that AREN'T apostrophes.
-            let words: Vec<&str> = unclean_line.splitn(unclean_line.len() + 1, |c: char| !(c.is_alphabetic()) && c != '\'').collect();
-
-            for word in words {
-                match clean_word(word) {
-                    Some(cleaned_word) => {
-                        increment_word(map, String::from(cleaned_word)
-                            .to_lowercase());
-                    }
-                    None => {
-                        continue;
-                    }
+            for word in split_line(&unclean_line) {
+                if let Some(normalized) = clean_and_normalize(word, config) {
+                    increment_word(map, normalized);
                 }
             }
         }
     }
 }
 
-fn clean_word(word: &str) -> Option<&str> {
+pub(crate) fn clean_word(word: &str) -> Option<&str> {
     if word.is_empty() {
         None
     }
@@ -143,7 +253,7 @@ fn clean_word(word: &str) -> Option<&str> {
     }
 }
 
-fn increment_word(mut map: &mut CountTable, word: String) {
+pub(crate) fn increment_word(mut map: &mut CountTable, word: String) {
     *map.entry(word).or_insert(0) += 1;
 }
 
@@ -154,13 +264,14 @@ fn increment_word(mut map: &mut CountTable, word: String) {
 //correctly.
 mod read_words_tests {
     use super::{CountTable, read_words};
+    use crate::cli::Config;
     use std::io::{Read, Result};
 
     #[test]
     fn one_word_per_line() {
         let input = StringReader::new("Hello\nWorld".to_owned());
         let mut under_test = CountTable::new();
-        read_words(input, &mut under_test);
+        read_words(input, &mut under_test, &Config::default());
 
         let mut expected = CountTable::new();
         expected.insert("hello".to_owned(), 1);
@@ -173,7 +284,7 @@ mod read_words_tests {
     fn non_alphabetic() {
         let input = StringReader::new(".....&&*(*&( \n    %$#@Ok!!43424!".to_owned());
         let mut under_test = CountTable::new();
-        read_words(input, &mut under_test);
+        read_words(input, &mut under_test, &Config::default());
 
         let mut expected = CountTable::new();
         //Notice that Ok counts as a word, because non-alphabetic chars
@@ -186,7 +297,7 @@ mod read_words_tests {
     fn apostrophes() {
         let input = StringReader::new("Jesse 'jesse' 'jesse JESSE' '' ''Jesse".to_owned());
         let mut under_test = CountTable::new();
-        read_words(input, &mut under_test);
+        read_words(input, &mut under_test, &Config::default());
 
         let mut expected = CountTable::new();
         //Notice the last ''Jesse will not map to a word because
@@ -201,7 +312,7 @@ mod read_words_tests {
     fn acronymns() {
         let input = StringReader::new("U.S.A.".to_owned());
         let mut under_test = CountTable::new();
-        read_words(input, &mut under_test);
+        read_words(input, &mut under_test, &Config::default());
 
         let mut expected = CountTable::new();
         expected.insert("a".to_owned(), 1);
@@ -213,7 +324,7 @@ mod read_words_tests {
     fn one_letter_words() {
         let input = StringReader::new("a\ne\ni\n'o\n'u'".to_owned());
         let mut under_test = CountTable::new();
-        read_words(input, &mut under_test);
+        read_words(input, &mut under_test, &Config::default());
 
         let mut expected = CountTable::new();
         expected.insert("a".to_owned(), 1);
@@ -227,6 +338,41 @@ mod read_words_tests {
         assert_eq!(expected, under_test);
     }
 
+    #[test]
+    fn ignores_stop_words_after_cleaning() {
+        let input = StringReader::new("the cat sat on THE mat".to_owned());
+        let mut under_test = CountTable::new();
+        let mut config = Config::default();
+        config.ignore.insert("the".to_owned());
+        read_words(input, &mut under_test, &config);
+
+        let mut expected = CountTable::new();
+        expected.insert("cat".to_owned(), 1);
+        expected.insert("sat".to_owned(), 1);
+        expected.insert("on".to_owned(), 1);
+        expected.insert("mat".to_owned(), 1);
+
+        assert_eq!(expected, under_test);
+    }
+
+    #[test]
+    fn case_sensitive_keeps_original_case() {
+        let input = StringReader::new("Hello hello HELLO".to_owned());
+        let mut under_test = CountTable::new();
+        let config = Config {
+            case_sensitive: true,
+            ..Config::default()
+        };
+        read_words(input, &mut under_test, &config);
+
+        let mut expected = CountTable::new();
+        expected.insert("Hello".to_owned(), 1);
+        expected.insert("hello".to_owned(), 1);
+        expected.insert("HELLO".to_owned(), 1);
+
+        assert_eq!(expected, under_test);
+    }
+
 
     struct StringReader {
         contents: Vec<u8>,
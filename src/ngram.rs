@@ -0,0 +1,110 @@
+use crate::cli::Config;
+use crate::{clean_and_normalize, split_line};
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{BufRead, BufReader, Read};
+
+//Keyed on the n words in the window, in order, so "new york" and
+//"york new" are distinct entries and ties still sort consistently via
+//BTreeMap's lexicographic Vec<String> ordering.
+pub type NgramTable = BTreeMap<Vec<String>, usize>;
+
+//Mirrors read_words, but instead of counting each cleaned word on its
+//own, it slides a window of the last `n` cleaned words (persisted
+//across line boundaries) and counts the window itself once it's full.
+pub fn read_ngrams<R: Read>(reader: R, table: &mut NgramTable, n: usize, config: &Config) {
+    let mut lines = BufReader::new(reader).lines();
+    let mut window: VecDeque<String> = VecDeque::with_capacity(n);
+
+    while let Some(Ok(line)) = lines.next() {
+        for word in split_line(&line) {
+            let normalized = match clean_and_normalize(word, config) {
+                Some(normalized) => normalized,
+                None => continue,
+            };
+
+            window.push_back(normalized);
+            if window.len() > n {
+                window.pop_front();
+            }
+
+            if window.len() == n {
+                increment_ngram(table, window.iter().cloned().collect());
+            }
+        }
+    }
+}
+
+fn increment_ngram(table: &mut NgramTable, ngram: Vec<String>) {
+    *table.entry(ngram).or_insert(0) += 1;
+}
+
+#[cfg(test)]
+mod read_ngrams_tests {
+    use super::{read_ngrams, NgramTable};
+    use crate::cli::Config;
+
+    #[test]
+    fn bigrams_across_one_line() {
+        let input = "the cat sat on the mat".as_bytes();
+        let mut under_test = NgramTable::new();
+        read_ngrams(input, &mut under_test, 2, &Config::default());
+
+        let mut expected = NgramTable::new();
+        expected.insert(vec!["the".to_owned(), "cat".to_owned()], 1);
+        expected.insert(vec!["cat".to_owned(), "sat".to_owned()], 1);
+        expected.insert(vec!["sat".to_owned(), "on".to_owned()], 1);
+        expected.insert(vec!["on".to_owned(), "the".to_owned()], 1);
+        expected.insert(vec!["the".to_owned(), "mat".to_owned()], 1);
+
+        assert_eq!(expected, under_test);
+    }
+
+    #[test]
+    fn window_spans_line_boundaries() {
+        let input = "new\nyork".as_bytes();
+        let mut under_test = NgramTable::new();
+        read_ngrams(input, &mut under_test, 2, &Config::default());
+
+        let mut expected = NgramTable::new();
+        expected.insert(vec!["new".to_owned(), "york".to_owned()], 1);
+
+        assert_eq!(expected, under_test);
+    }
+
+    #[test]
+    fn trigrams() {
+        let input = "one two three one two three".as_bytes();
+        let mut under_test = NgramTable::new();
+        read_ngrams(input, &mut under_test, 3, &Config::default());
+
+        let mut expected = NgramTable::new();
+        expected.insert(
+            vec!["one".to_owned(), "two".to_owned(), "three".to_owned()],
+            2,
+        );
+        expected.insert(
+            vec!["two".to_owned(), "three".to_owned(), "one".to_owned()],
+            1,
+        );
+        expected.insert(
+            vec!["three".to_owned(), "one".to_owned(), "two".to_owned()],
+            1,
+        );
+
+        assert_eq!(expected, under_test);
+    }
+
+    #[test]
+    fn filtered_words_are_not_part_of_the_window() {
+        let input = "the cat sat".as_bytes();
+        let mut under_test = NgramTable::new();
+        let mut config = Config::default();
+        config.ignore.insert("the".to_owned());
+        read_ngrams(input, &mut under_test, 2, &config);
+
+        let mut expected = NgramTable::new();
+        expected.insert(vec!["cat".to_owned(), "sat".to_owned()], 1);
+
+        assert_eq!(expected, under_test);
+    }
+}
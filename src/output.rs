@@ -0,0 +1,205 @@
+//How a frequency table should be sorted before printing. All orders
+//that don't fully order the table (every order but Alpha) tiebreak
+//alphabetically so output is deterministic regardless of BTreeMap
+//iteration order.
+#[derive(Debug, PartialEq)]
+pub enum SortOrder {
+    CountDesc,
+    CountAsc,
+    Alpha,
+    Length,
+}
+
+//How a frequency table should be rendered.
+#[derive(Debug, PartialEq)]
+pub enum OutputFormat {
+    Plain,
+    Csv,
+    Tsv,
+    Json,
+}
+
+//Sorts entries according to `order`, using `label` to turn a key into
+//the string that's actually compared/printed (a word for the unigram
+//table, a space-joined n-gram for the n-gram table).
+pub fn sort_entries<'a, K>(
+    mut entries: Vec<(&'a K, &'a usize)>,
+    order: &SortOrder,
+    label: &impl Fn(&K) -> String,
+) -> Vec<(&'a K, &'a usize)> {
+    match order {
+        SortOrder::CountDesc => {
+            entries.sort_by(|a, b| b.1.cmp(a.1).then_with(|| label(a.0).cmp(&label(b.0))))
+        }
+        SortOrder::CountAsc => {
+            entries.sort_by(|a, b| a.1.cmp(b.1).then_with(|| label(a.0).cmp(&label(b.0))))
+        }
+        SortOrder::Alpha => entries.sort_by_key(|a| label(a.0)),
+        SortOrder::Length => entries.sort_by(|a, b| {
+            let (label_a, label_b) = (label(a.0), label(b.0));
+            label_a.len().cmp(&label_b.len()).then_with(|| label_a.cmp(&label_b))
+        }),
+    }
+
+    entries
+}
+
+//Renders already-sorted entries in the requested format.
+pub fn render<K>(
+    entries: &[(&K, &usize)],
+    format: &OutputFormat,
+    label: &impl Fn(&K) -> String,
+) -> String {
+    match format {
+        OutputFormat::Plain => entries
+            .iter()
+            .map(|(word, count)| format!("{}: {}", label(word), count))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        OutputFormat::Csv => render_delimited(entries, label, ','),
+        OutputFormat::Tsv => render_delimited(entries, label, '\t'),
+        OutputFormat::Json => render_json(entries, label),
+    }
+}
+
+fn render_delimited<K>(
+    entries: &[(&K, &usize)],
+    label: &impl Fn(&K) -> String,
+    delimiter: char,
+) -> String {
+    let mut lines = vec![format!("word{}count", delimiter)];
+
+    for (word, count) in entries {
+        lines.push(format!(
+            "{}{}{}",
+            escape_delimited(&label(word), delimiter),
+            delimiter,
+            count
+        ));
+    }
+
+    lines.join("\n")
+}
+
+//RFC 4180-style quoting: wrap the field in quotes (doubling any quotes
+//already inside) if it contains the delimiter, a quote, or a newline.
+fn escape_delimited(field: &str, delimiter: char) -> String {
+    if field.contains(delimiter) || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn render_json<K>(entries: &[(&K, &usize)], label: &impl Fn(&K) -> String) -> String {
+    let objects: Vec<String> = entries
+        .iter()
+        .map(|(word, count)| format!("{{\"word\": \"{}\", \"count\": {}}}", escape_json(&label(word)), count))
+        .collect();
+
+    format!("[{}]", objects.join(", "))
+}
+
+fn escape_json(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+
+    for c in field.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod output_tests {
+    use super::{render, sort_entries, OutputFormat, SortOrder};
+
+    #[allow(clippy::ptr_arg)]
+    fn label(word: &String) -> String {
+        word.clone()
+    }
+
+    fn entries(pairs: &[(&'static str, usize)]) -> Vec<(String, usize)> {
+        pairs.iter().map(|(w, c)| (w.to_string(), *c)).collect()
+    }
+
+    #[test]
+    fn count_desc_tiebreaks_alphabetically() {
+        let owned = entries(&[("zebra", 2), ("apple", 2), ("mango", 3)]);
+        let refs: Vec<_> = owned.iter().map(|(w, c)| (w, c)).collect();
+
+        let sorted = sort_entries(refs, &SortOrder::CountDesc, &label);
+        let words: Vec<&str> = sorted.iter().map(|(w, _)| w.as_str()).collect();
+
+        assert_eq!(vec!["mango", "apple", "zebra"], words);
+    }
+
+    #[test]
+    fn alpha_ignores_count() {
+        let owned = entries(&[("zebra", 5), ("apple", 1)]);
+        let refs: Vec<_> = owned.iter().map(|(w, c)| (w, c)).collect();
+
+        let sorted = sort_entries(refs, &SortOrder::Alpha, &label);
+        let words: Vec<&str> = sorted.iter().map(|(w, _)| w.as_str()).collect();
+
+        assert_eq!(vec!["apple", "zebra"], words);
+    }
+
+    #[test]
+    fn length_tiebreaks_alphabetically() {
+        let owned = entries(&[("bb", 1), ("aa", 1), ("c", 1)]);
+        let refs: Vec<_> = owned.iter().map(|(w, c)| (w, c)).collect();
+
+        let sorted = sort_entries(refs, &SortOrder::Length, &label);
+        let words: Vec<&str> = sorted.iter().map(|(w, _)| w.as_str()).collect();
+
+        assert_eq!(vec!["c", "aa", "bb"], words);
+    }
+
+    #[test]
+    fn plain_format() {
+        let owned = entries(&[("cat", 2)]);
+        let refs: Vec<_> = owned.iter().map(|(w, c)| (w, c)).collect();
+
+        assert_eq!("cat: 2", render(&refs, &OutputFormat::Plain, &label));
+    }
+
+    #[test]
+    fn csv_format_quotes_fields_with_commas() {
+        let owned = entries(&[("hello, world", 1)]);
+        let refs: Vec<_> = owned.iter().map(|(w, c)| (w, c)).collect();
+
+        assert_eq!(
+            "word,count\n\"hello, world\",1",
+            render(&refs, &OutputFormat::Csv, &label)
+        );
+    }
+
+    #[test]
+    fn tsv_format() {
+        let owned = entries(&[("cat", 2)]);
+        let refs: Vec<_> = owned.iter().map(|(w, c)| (w, c)).collect();
+
+        assert_eq!(
+            "word\tcount\ncat\t2",
+            render(&refs, &OutputFormat::Tsv, &label)
+        );
+    }
+
+    #[test]
+    fn json_format_escapes_quotes() {
+        let owned = entries(&[("she said \"hi\"", 1)]);
+        let refs: Vec<_> = owned.iter().map(|(w, c)| (w, c)).collect();
+
+        assert_eq!(
+            "[{\"word\": \"she said \\\"hi\\\"\", \"count\": 1}]",
+            render(&refs, &OutputFormat::Json, &label)
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use crate::cli::Config;
+use crate::{clean_and_normalize, split_line, CountTable};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+
+//How many lines each worker chunk owns. Large enough that a chunk is
+//worth shipping to a thread, small enough that chunks stay numerous
+//enough to balance across however many threads are available.
+const LINES_PER_CHUNK: usize = 10_000;
+
+//Parallel counterpart to read_words: streams the input into
+//line-aligned chunks (so no word is ever torn across a chunk boundary)
+//as workers ask for them, rather than buffering the whole input into
+//memory up front, tokenizes and cleans each chunk into a local HashMap
+//on a worker thread, then sums the partial maps into one CountTable.
+//Reduction is order-independent addition, so the result is identical
+//to the sequential path regardless of how chunks were scheduled.
+pub fn read_words_parallel<R: Read + Send>(reader: R, config: &Config, threads: usize) -> CountTable {
+    let chunks = ChunkedLines::new(reader, LINES_PER_CHUNK);
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .expect("failed to start thread pool");
+
+    let partials: Vec<HashMap<String, usize>> = pool.install(|| {
+        chunks
+            .par_bridge()
+            .map(|chunk| count_chunk(&chunk, config))
+            .collect()
+    });
+
+    let mut table = CountTable::new();
+    for partial in partials {
+        for (word, count) in partial {
+            *table.entry(word).or_insert(0) += count;
+        }
+    }
+
+    table
+}
+
+fn count_chunk(chunk: &[String], config: &Config) -> HashMap<String, usize> {
+    let mut local = HashMap::new();
+
+    for line in chunk {
+        for word in split_line(line) {
+            if let Some(normalized) = clean_and_normalize(word, config) {
+                *local.entry(normalized).or_insert(0) += 1;
+            }
+        }
+    }
+
+    local
+}
+
+//Lazily groups a reader's lines into chunks of up to `lines_per_chunk`
+//lines each, reading only as far ahead as whichever worker calls
+//next() needs. par_bridge() pulls chunks through this one at a time
+//(serialized behind a lock), so only the chunks that workers are
+//currently counting, at most one per thread, are held in memory no
+//matter how large the input is.
+struct ChunkedLines<R: Read> {
+    lines: std::io::Lines<BufReader<R>>,
+    lines_per_chunk: usize,
+    done: bool,
+}
+
+impl<R: Read> ChunkedLines<R> {
+    fn new(reader: R, lines_per_chunk: usize) -> Self {
+        ChunkedLines {
+            lines: BufReader::new(reader).lines(),
+            lines_per_chunk,
+            done: false,
+        }
+    }
+}
+
+impl<R: Read> Iterator for ChunkedLines<R> {
+    type Item = Vec<String>;
+
+    fn next(&mut self) -> Option<Vec<String>> {
+        if self.done {
+            return None;
+        }
+
+        let mut chunk = Vec::with_capacity(self.lines_per_chunk);
+
+        while chunk.len() < self.lines_per_chunk {
+            match self.lines.next() {
+                Some(Ok(line)) => chunk.push(line),
+                //Mirrors read_words/read_ngrams, which stop reading
+                //entirely at the first unreadable line rather than
+                //skipping it and reading on; flush whatever's left in
+                //this chunk but never produce another one.
+                Some(Err(_)) => {
+                    self.done = true;
+                    break;
+                }
+                None => break,
+            }
+        }
+
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(chunk)
+        }
+    }
+}
+
+#[cfg(test)]
+mod read_words_parallel_tests {
+    use super::read_words_parallel;
+    use crate::cli::Config;
+    use crate::CountTable;
+
+    #[test]
+    fn matches_sequential_output() {
+        let input = "the cat sat on the mat\nthe cat ran\n".repeat(50);
+
+        let mut sequential = CountTable::new();
+        crate::read_words(input.as_bytes(), &mut sequential, &Config::default());
+
+        let parallel = read_words_parallel(input.as_bytes(), &Config::default(), 4);
+
+        assert_eq!(sequential, parallel);
+    }
+
+    #[test]
+    fn respects_ignore_and_case_sensitivity() {
+        let input = "The The the";
+        let mut config = Config::default();
+        config.ignore.insert("the".to_owned());
+
+        let table = read_words_parallel(input.as_bytes(), &config, 2);
+
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn chunk_boundaries_do_not_split_words() {
+        //Feed more lines than LINES_PER_CHUNK (25,000 vs 10,000)
+        //through the public entry point so the input spans several
+        //chunks; each line is a complete token, so no word can be torn
+        //regardless of how lines are grouped into chunks.
+        let input = "alpha\n".repeat(25_000);
+
+        let table = read_words_parallel(input.as_bytes(), &Config::default(), 3);
+
+        assert_eq!(Some(&25_000), table.get("alpha"));
+    }
+
+    #[test]
+    fn stops_at_first_unreadable_line_like_sequential() {
+        //read_words (and read_ngrams) stop reading entirely at the
+        //first line that isn't valid UTF-8; the parallel path must
+        //match that instead of skipping the bad line and reading on.
+        let mut input = Vec::new();
+        input.extend_from_slice(b"hello world\n");
+        input.extend_from_slice(&[0xFF, 0xFE, b'\n']);
+        input.extend_from_slice(b"here more words\n");
+
+        let mut sequential = CountTable::new();
+        crate::read_words(input.as_slice(), &mut sequential, &Config::default());
+
+        let parallel = read_words_parallel(input.as_slice(), &Config::default(), 2);
+
+        assert_eq!(sequential, parallel);
+    }
+
+    #[test]
+    fn chunked_lines_yields_chunks_lazily() {
+        //Each call to next() should only pull lines_per_chunk lines
+        //through the reader rather than draining it all up front, so
+        //a chunk boundary mid-stream still leaves the rest unread.
+        use super::ChunkedLines;
+
+        let input = "a\nb\nc\nd\ne\n";
+        let mut chunks = ChunkedLines::new(input.as_bytes(), 2);
+
+        assert_eq!(Some(vec!["a".to_owned(), "b".to_owned()]), chunks.next());
+        assert_eq!(Some(vec!["c".to_owned(), "d".to_owned()]), chunks.next());
+        assert_eq!(Some(vec!["e".to_owned()]), chunks.next());
+        assert_eq!(None, chunks.next());
+    }
+
+    #[test]
+    fn chunked_lines_stops_at_unreadable_line() {
+        use super::ChunkedLines;
+
+        let mut input = Vec::new();
+        input.extend_from_slice(b"a\n");
+        input.extend_from_slice(&[0xFF, 0xFE, b'\n']);
+        input.extend_from_slice(b"b\n");
+
+        let mut chunks = ChunkedLines::new(input.as_slice(), 10);
+
+        assert_eq!(Some(vec!["a".to_owned()]), chunks.next());
+        assert_eq!(None, chunks.next());
+    }
+}
@@ -0,0 +1,245 @@
+use crate::cli::Config;
+use crate::ngram::NgramTable;
+use crate::{increment_word, CountTable};
+use std::io::{BufRead, BufReader, Read};
+
+//Splits a run of characters with no natural word boundaries (e.g.
+//Chinese) into the most probable sequence of known words, via dynamic
+//programming over character positions. For each position `i`, every
+//candidate word `text[j..i]` with `j` within `max_word_len` of `i` is
+//scored, the best cumulative score and its back-pointer are kept, and
+//the segmentation is reconstructed by walking the back-pointers from
+//the end.
+pub fn segment(
+    text: &str,
+    unigrams: &CountTable,
+    bigrams: &NgramTable,
+    max_word_len: usize,
+) -> Vec<String> {
+    //Avoid dividing by zero when the corpus is empty; every word would
+    //be "unknown" anyway, so any positive total gives the same ranking.
+    let total: usize = unigrams.values().sum::<usize>().max(1);
+    let chars: Vec<char> = text.chars().collect();
+    let char_count = chars.len();
+
+    //best_score[i] is the best score for segmenting chars[0..i];
+    //best_prev[i] is where the last word in that segmentation starts.
+    let mut best_score = vec![f64::NEG_INFINITY; char_count + 1];
+    let mut best_prev = vec![0usize; char_count + 1];
+    best_score[0] = 0.0;
+
+    for i in 1..=char_count {
+        let earliest_start = i.saturating_sub(max_word_len);
+
+        for j in earliest_start..i {
+            if best_score[j].is_infinite() {
+                continue;
+            }
+
+            let word: String = chars[j..i].iter().collect();
+            let word_score = if j == 0 {
+                unigram_log_prob(&word, unigrams, total)
+            } else {
+                let prev_word: String = chars[best_prev[j]..j].iter().collect();
+                bigram_log_prob(&prev_word, &word, unigrams, bigrams, total)
+            };
+
+            let score = best_score[j] + word_score;
+            if score > best_score[i] {
+                best_score[i] = score;
+                best_prev[i] = j;
+            }
+        }
+    }
+
+    let mut words = Vec::new();
+    let mut i = char_count;
+    while i > 0 {
+        let j = best_prev[i];
+        words.push(chars[j..i].iter().collect::<String>());
+        i = j;
+    }
+    words.reverse();
+
+    words
+}
+
+//log(count(word) / total), or a length-penalized fallback for words
+//never seen in the training corpus.
+fn unigram_log_prob(word: &str, unigrams: &CountTable, total: usize) -> f64 {
+    match unigrams.get(word) {
+        Some(&count) if count > 0 => (count as f64 / total as f64).ln(),
+        _ => unknown_word_penalty(word, total),
+    }
+}
+
+//log(P(word | prev_word)), backing off to the plain unigram
+//probability when the pair was never seen together.
+fn bigram_log_prob(
+    prev_word: &str,
+    word: &str,
+    unigrams: &CountTable,
+    bigrams: &NgramTable,
+    total: usize,
+) -> f64 {
+    let key = vec![prev_word.to_owned(), word.to_owned()];
+
+    match (bigrams.get(&key), unigrams.get(prev_word)) {
+        (Some(&bigram_count), Some(&prev_count)) if bigram_count > 0 && prev_count > 0 => {
+            (bigram_count as f64 / prev_count as f64).ln()
+        }
+        _ => unigram_log_prob(word, unigrams, total),
+    }
+}
+
+//Longer unknown substrings are exponentially less likely to be a
+//single real word, so the log-probability drops by ln(10) for each
+//extra character.
+fn unknown_word_penalty(word: &str, total: usize) -> f64 {
+    let len = word.chars().count() as i32;
+    (10.0 / (total as f64 * 10f64.powi(len))).ln()
+}
+
+//Mirrors read_words, but for space-free input: each line is segmented
+//via `segment` instead of split on non-alphabetic characters before
+//the resulting words are fed into increment_word.
+pub fn read_segmented<R: Read>(
+    reader: R,
+    map: &mut CountTable,
+    unigrams: &CountTable,
+    bigrams: &NgramTable,
+    max_word_len: usize,
+    config: &Config,
+) {
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(Ok(line)) = lines.next() {
+        //unigrams/bigrams are built via clean_and_normalize, which
+        //lowercases by default, so the line must be lowercased the
+        //same way before scoring or non-lowercase input will never
+        //match the corpus and fall back to the unknown-word penalty.
+        let normalized_line = if config.case_sensitive {
+            line
+        } else {
+            line.to_lowercase()
+        };
+
+        for word in segment(&normalized_line, unigrams, bigrams, max_word_len) {
+            if config.ignore.contains(&word) {
+                continue;
+            }
+
+            increment_word(map, word);
+        }
+    }
+}
+
+#[cfg(test)]
+mod segment_tests {
+    use super::{read_segmented, segment};
+    use crate::cli::Config;
+    use crate::ngram::NgramTable;
+    use crate::CountTable;
+
+    fn unigrams(counts: &[(&str, usize)]) -> CountTable {
+        counts
+            .iter()
+            .map(|(word, count)| (word.to_string(), *count))
+            .collect()
+    }
+
+    fn bigrams(counts: &[(&str, &str, usize)]) -> NgramTable {
+        counts
+            .iter()
+            .map(|(first, second, count)| (vec![first.to_string(), second.to_string()], *count))
+            .collect()
+    }
+
+    #[test]
+    fn splits_known_words() {
+        let unigrams = unigrams(&[("new", 100), ("york", 100), ("newyork", 1)]);
+        let bigrams = bigrams(&[("new", "york", 50)]);
+
+        assert_eq!(
+            vec!["new".to_owned(), "york".to_owned()],
+            segment("newyork", &unigrams, &bigrams, 10)
+        );
+    }
+
+    #[test]
+    fn falls_back_to_unigram_when_bigram_is_unseen() {
+        let unigrams = unigrams(&[("un", 100), ("seen", 100)]);
+        let bigrams = NgramTable::new();
+
+        assert_eq!(
+            vec!["un".to_owned(), "seen".to_owned()],
+            segment("unseen", &unigrams, &bigrams, 10)
+        );
+    }
+
+    #[test]
+    fn respects_max_word_len() {
+        //"abcd" isn't in the vocabulary at all; with max_word_len
+        //capped at 1 only single-character candidates are scored, so
+        //the result must be four one-character words that still join
+        //back into the input.
+        let unigrams = unigrams(&[("zzz", 10)]);
+        let bigrams = NgramTable::new();
+
+        let words = segment("abcd", &unigrams, &bigrams, 1);
+        assert_eq!(4, words.len());
+        assert_eq!("abcd", words.join(""));
+    }
+
+    #[test]
+    fn lowercases_input_before_scoring_against_corpus() {
+        //The corpus is built through clean_and_normalize, which
+        //lowercases by default, so upper-case input must be lowercased
+        //before segmenting or it will never match "new"/"york" and is
+        //kept as one seven-letter "unknown" word instead.
+        let unigrams = unigrams(&[("new", 100), ("york", 100)]);
+        let bigrams = bigrams(&[("new", "york", 50)]);
+
+        let mut map = CountTable::new();
+        read_segmented(
+            "NEWYORK".as_bytes(),
+            &mut map,
+            &unigrams,
+            &bigrams,
+            10,
+            &Config::default(),
+        );
+
+        let mut expected = CountTable::new();
+        expected.insert("new".to_owned(), 1);
+        expected.insert("york".to_owned(), 1);
+
+        assert_eq!(expected, map);
+    }
+
+    #[test]
+    fn case_sensitive_segments_without_lowercasing() {
+        let unigrams = unigrams(&[("New", 100), ("York", 100)]);
+        let bigrams = bigrams(&[("New", "York", 50)]);
+        let config = Config {
+            case_sensitive: true,
+            ..Config::default()
+        };
+
+        let mut map = CountTable::new();
+        read_segmented(
+            "NewYork".as_bytes(),
+            &mut map,
+            &unigrams,
+            &bigrams,
+            10,
+            &config,
+        );
+
+        let mut expected = CountTable::new();
+        expected.insert("New".to_owned(), 1);
+        expected.insert("York".to_owned(), 1);
+
+        assert_eq!(expected, map);
+    }
+}